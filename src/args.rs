@@ -16,7 +16,26 @@ Get passwords in the Financial folder that haven't been changed in the last year
     ArgGroup::new("filter")
     .args(["only_unmodified", "only_modified"]),
 ))]
+#[command(group(
+    ArgGroup::new("revision_filter")
+    .args(["changed_since", "unchanged_since"]),
+))]
 pub struct Args {
+    /// Blame line 1 of each password individually instead of a single bulk
+    /// `git log` pass.
+    ///
+    /// By default pass-age makes one `git log --name-only` pass over the
+    /// whole store and uses the last commit to touch each file, which is
+    /// much faster on large stores but reports whole-file mtime rather
+    /// than the blame time of line 1 (the password itself). `--precise`
+    /// falls back to blaming line 1 of every password individually,
+    /// in-process via `gix` unless `--ignore-rev`/`--ignore-revs-file` are
+    /// also given, in which case it shells out to `git blame` (the only
+    /// way to honor those). Required when combined with `--ignore-rev` or
+    /// `--ignore-revs-file`, which the bulk mode cannot honor.
+    #[arg(long, action)]
+    pub precise: bool,
+
     /// Ignore changes made by the revision when assigning blame.
     ///
     /// Ignore changes made by the revision when assigning blame, as if the
@@ -29,7 +48,7 @@ pub struct Args {
     /// `blame.markUnblamableLines` config option is set, then those lines touched
     /// by an ignored commit that we could not attribute to another revision
     /// are marked with a `*`.
-    #[arg(long, value_name = "rev")]
+    #[arg(long, value_name = "rev", requires = "precise")]
     pub ignore_rev: Vec<String>,
 
     /// Ignore revisions listed in `file`.
@@ -38,7 +57,7 @@ pub struct Args {
     /// `fsck.skipList`. This option may be repeated, and these files will be
     /// processed after any files specified with the `blame.ignoreRevsFile`
     /// config option.
-    #[arg(long, value_name = "file", value_parser = clap::value_parser!(ClioPath).exists().is_file())]
+    #[arg(long, value_name = "file", value_parser = clap::value_parser!(ClioPath).exists().is_file(), requires = "precise")]
     pub ignore_revs_file: Vec<ClioPath>,
 
     /// Only display the passwords in the store that have not been modified.
@@ -54,6 +73,20 @@ pub struct Args {
     #[arg(long, value_name = "date", value_parser= parse_duration, requires="filter")]
     pub since: Option<chrono::Duration>,
 
+    /// List passwords whose secret (line 1) changed between `<rev>` and HEAD.
+    ///
+    /// Useful after a suspected compromise commit to enumerate exactly which
+    /// credentials need rotation. Unlike `--since`, which filters on
+    /// author-time duration, this walks the line-1 history in `<rev>..HEAD`
+    /// for each matched password and composes with `--sort-by`/`--reverse`.
+    #[arg(long, value_name = "rev")]
+    pub changed_since: Option<String>,
+
+    /// List passwords whose secret (line 1) has *not* changed between
+    /// `<rev>` and HEAD. The inverse of `--changed-since`.
+    #[arg(long, value_name = "rev")]
+    pub unchanged_since: Option<String>,
+
     /// Reverse the sort order
     #[arg(short, long, action=ArgAction::SetFalse)]
     pub reverse: bool,
@@ -61,17 +94,69 @@ pub struct Args {
     #[arg(short, long, value_enum, default_value_t=SortBy::Name)]
     pub sort_by: SortBy,
 
+    /// Increase logging verbosity. May be repeated (`-v` for info, `-vv`
+    /// for debug, `-vvv` for trace).
+    #[arg(short, long, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Decrease logging verbosity. May be repeated (`-q` silences warnings,
+    /// `-qq` silences everything).
+    #[arg(short, long, action = ArgAction::Count, conflicts_with = "verbose")]
+    pub quiet: u8,
+
+    /// Output format for the matched passwords.
+    #[arg(long, value_enum, default_value_t=OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Run `cmd` once per matching password, with `{}` replaced by the
+    /// pass name, e.g. `--exec 'pass generate {} 20'`.
+    ///
+    /// The command is run through the shell, so it may use pipes and
+    /// redirection. A nonzero exit is reported as a warning for that
+    /// password without aborting the rest of the run.
+    #[arg(long, value_name = "cmd")]
+    pub exec: Option<String>,
+
     /// The passwords that match pass-names
     #[arg(value_name = "pass-names")]
     pub file: Vec<PathBuf>,
 }
 
+impl Args {
+    /// The `log` level filter implied by `--verbose`/`--quiet`.
+    ///
+    /// Defaults to `Warn`, so per-file blame failures are visible without
+    /// any flags but the per-search-path progress lines are not.
+    pub fn log_level_filter(&self) -> log::LevelFilter {
+        if self.quiet > 0 {
+            match self.quiet {
+                1 => log::LevelFilter::Error,
+                _ => log::LevelFilter::Off,
+            }
+        } else {
+            match self.verbose {
+                0 => log::LevelFilter::Warn,
+                1 => log::LevelFilter::Info,
+                2 => log::LevelFilter::Debug,
+                _ => log::LevelFilter::Trace,
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 pub enum SortBy {
     Name,
     LastModified,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
 fn parse_duration(arg: &str) -> Result<chrono::Duration, std::num::ParseIntError> {
     let duration = arg.parse::<humantime::Duration>().unwrap();
     let seconds = duration
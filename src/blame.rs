@@ -0,0 +1,295 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use clio::ClioPath;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug)]
+pub struct BlameData {
+    pub pass_filename: PathBuf,
+    pub last_modified: Duration,
+    /// Unix timestamp of the commit `last_modified` is measured from,
+    /// captured once at blame time so machine-readable output doesn't drift
+    /// with however long the rest of the run takes.
+    pub last_modified_unix: i64,
+    pub found_previous_commit: bool,
+}
+
+impl BlameData {
+    fn new(pass_filename: PathBuf, author_time: DateTime<Utc>, found_previous: bool) -> Self {
+        Self {
+            pass_filename,
+            last_modified: Utc::now() - author_time,
+            last_modified_unix: author_time.timestamp(),
+            found_previous_commit: found_previous,
+        }
+    }
+}
+
+/// Blames line 1 (the password) of a single password file and reports its age.
+///
+/// Implementations are selected once per run in `main`, based on whether
+/// `--ignore-rev`/`--ignore-revs-file` were given, since [`GixBackend`]
+/// doesn't support either.
+pub trait GitBackend {
+    fn blame_age(&self, pass_filename: &Path) -> Result<BlameData>;
+}
+
+/// Blames by shelling out to `git blame -pL ,1` and parsing the porcelain
+/// output. Slower than [`GixBackend`] and sensitive to the installed git's
+/// porcelain format, but supports `--ignore-rev`/`--ignore-revs-file`.
+pub struct SubprocessBackend<'a> {
+    pub ignore_rev: &'a [String],
+    pub ignore_revs_file: &'a [ClioPath],
+}
+
+impl GitBackend for SubprocessBackend<'_> {
+    fn blame_age(&self, pass_filename: &Path) -> Result<BlameData> {
+        //Let's build up the git command
+        let mut command = Command::new("git");
+
+        //Blame in the porcalain format the first line (the password)
+        command.args(["blame", "-pL", ",1"]);
+
+        // Add in the arguments, if given
+        for ignore_rev in self.ignore_rev {
+            command.args(["--ignore-rev", ignore_rev]);
+        }
+
+        for ignore_revs_file in self.ignore_revs_file {
+            command.args([
+                "--ignore-revs-file",
+                &ignore_revs_file.path().as_os_str().to_string_lossy(),
+            ]);
+        }
+
+        command.args(["--", &pass_filename.as_os_str().to_string_lossy()]);
+
+        let git_output = command.output()?;
+
+        if !git_output.status.success() {
+            return Err(anyhow!("{}", String::from_utf8(git_output.stderr)?));
+        }
+
+        let git_stdout = String::from_utf8(git_output.stdout)?;
+
+        let mut author_time_dt = None;
+        let mut found_previous = false;
+
+        for line in git_stdout.lines() {
+            if line.starts_with("author-time") {
+                let author_time = line
+                    .split_ascii_whitespace()
+                    .last()
+                    .with_context(|| format!("Unable to get author-time value from: {line}"))?;
+                author_time_dt = Some(DateTime::<Utc>::from_utc(
+                    NaiveDateTime::parse_from_str(author_time, "%s")
+                        .with_context(|| format!("Unable to parse timestamp: {author_time}"))?,
+                    Utc,
+                ));
+            } else if line.starts_with("previous") {
+                found_previous = true;
+            }
+        }
+        if let Some(author_time_dt) = author_time_dt {
+            Ok(BlameData::new(
+                pass_filename.to_path_buf().with_extension(""),
+                author_time_dt,
+                found_previous,
+            ))
+        } else {
+            Err(anyhow!("Unable to find the author-time"))
+        }
+    }
+}
+
+/// Blames in-process via `gix`, avoiding a `git` subprocess per password and
+/// any dependency on a `git` executable on `PATH`.
+///
+/// Doesn't support `--ignore-rev`/`--ignore-revs-file`; `main` falls back to
+/// [`SubprocessBackend`] when either is requested.
+pub struct GixBackend {
+    repo: gix::Repository,
+}
+
+impl GixBackend {
+    pub fn open(password_store_dir: &Path) -> Result<Self> {
+        let repo = gix::open(password_store_dir).with_context(|| {
+            format!(
+                "Unable to open git repository at {}",
+                password_store_dir.display()
+            )
+        })?;
+        Ok(Self { repo })
+    }
+}
+
+impl GitBackend for GixBackend {
+    fn blame_age(&self, pass_filename: &Path) -> Result<BlameData> {
+        let head_id = self.repo.head_id()?;
+        let blame = self
+            .repo
+            .blame_file(pass_filename, gix::blame::Options::default(), head_id.detach())
+            .with_context(|| format!("Unable to blame {}", pass_filename.display()))?;
+
+        let hunk = blame
+            .entries
+            .first()
+            .with_context(|| format!("No blame entries for {}", pass_filename.display()))?;
+        let commit = self.repo.find_commit(hunk.commit_id)?;
+        let author_time = commit.time()?;
+        let author_time_dt = DateTime::<Utc>::from_utc(
+            NaiveDateTime::from_timestamp_opt(author_time.seconds, 0)
+                .with_context(|| format!("Unable to parse timestamp: {}", author_time.seconds))?,
+            Utc,
+        );
+        // Matches the subprocess backend's use of blame's `previous` marker:
+        // the password was changed after it was added only if this path
+        // already existed in the first parent's tree.
+        // A parent commit existing in the DAG isn't enough on its own -
+        // almost every file-adding commit still has ancestors elsewhere in
+        // the repository.
+        let found_previous = match commit.parent_ids().next() {
+            Some(parent_id) => self
+                .repo
+                .find_commit(parent_id)?
+                .tree()?
+                .lookup_entry_by_path(pass_filename)
+                .with_context(|| {
+                    format!(
+                        "Unable to look up {} in parent tree",
+                        pass_filename.display()
+                    )
+                })?
+                .is_some(),
+            None => false,
+        };
+
+        Ok(BlameData::new(
+            pass_filename.to_path_buf().with_extension(""),
+            author_time_dt,
+            found_previous,
+        ))
+    }
+}
+
+/// Builds a `pass_filename -> (commit unix time, found previous commit)` map
+/// for every file in the store in a single `git log` invocation.
+///
+/// This walks `git log --name-only` newest-first and records the first
+/// (i.e. most recent) commit touching each path; a path is seen a second
+/// time only if it was changed again in an older commit, which is exactly
+/// when `found_previous_commit` should be `true`.
+///
+/// Note this is whole-file mtime, not the blame time of line 1 (the
+/// password itself): a commit that only touches metadata lines below the
+/// password will still bump the reported age. Use `--precise` for
+/// line-accurate results at the cost of one blame per password.
+pub fn get_bulk_blame_data() -> Result<HashMap<PathBuf, (i64, bool)>> {
+    let git_output = Command::new("git")
+        .args([
+            "log",
+            "--pretty=format:%H%x00%ad",
+            "--date=unix",
+            "--name-only",
+            "--diff-filter=AM",
+        ])
+        .output()?;
+
+    if !git_output.status.success() {
+        return Err(anyhow!("{}", String::from_utf8(git_output.stderr)?));
+    }
+
+    let git_stdout = String::from_utf8(git_output.stdout)?;
+
+    let mut ages = HashMap::<PathBuf, (i64, bool)>::new();
+    let mut commit_time = None;
+
+    for line in git_stdout.lines() {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some((_hash, timestamp)) = line.split_once('\0') {
+            commit_time = Some(
+                timestamp
+                    .parse::<i64>()
+                    .with_context(|| format!("Unable to parse timestamp: {timestamp}"))?,
+            );
+            continue;
+        }
+        let Some(commit_time) = commit_time else {
+            continue;
+        };
+        match ages.entry(PathBuf::from(line)) {
+            Entry::Vacant(entry) => {
+                entry.insert((commit_time, false));
+            }
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().1 = true;
+            }
+        }
+    }
+
+    Ok(ages)
+}
+
+/// Reports whether the secret (line 1) of `pass_filename` was changed by any
+/// commit in `<rev>..HEAD`.
+///
+/// Backs `--changed-since`/`--unchanged-since`, which are revision-based
+/// rather than duration-based: they answer "did this password rotate after
+/// a suspected compromise commit?" rather than "how long ago was it last
+/// touched?".
+pub fn secret_changed_since(pass_filename: &Path, rev: &str) -> Result<bool> {
+    let range = format!("{rev}..HEAD");
+
+    // `git log -L` exits 128 on an empty range (e.g. `rev` is HEAD itself),
+    // unlike plain `git log`/`git rev-list`, which just print nothing. An
+    // empty range trivially means nothing changed, so check for it first
+    // rather than letting the `-L` invocation below fail.
+    let rev_list_output = Command::new("git")
+        .args(["rev-list", "--max-count=1", &range])
+        .output()?;
+    if !rev_list_output.status.success() {
+        return Err(anyhow!("{}", String::from_utf8(rev_list_output.stderr)?));
+    }
+    if rev_list_output.stdout.is_empty() {
+        return Ok(false);
+    }
+
+    let git_output = Command::new("git")
+        .args([
+            "log",
+            "--pretty=format:%H",
+            &format!("-L1,1:{}", pass_filename.display()),
+            &range,
+        ])
+        .output()?;
+
+    if !git_output.status.success() {
+        return Err(anyhow!("{}", String::from_utf8(git_output.stderr)?));
+    }
+
+    Ok(!git_output.stdout.is_empty())
+}
+
+/// Builds `BlameData` for `pass_filename` from a unix timestamp and whether
+/// a previous commit was found, as produced by [`get_bulk_blame_data`].
+pub fn blame_data_from_timestamp(
+    pass_filename: &Path,
+    commit_time: i64,
+    found_previous: bool,
+) -> Result<BlameData> {
+    let author_time_dt = DateTime::<Utc>::from_utc(
+        NaiveDateTime::from_timestamp_opt(commit_time, 0)
+            .with_context(|| format!("Unable to parse timestamp: {commit_time}"))?,
+        Utc,
+    );
+    Ok(BlameData::new(
+        pass_filename.to_path_buf().with_extension(""),
+        author_time_dt,
+        found_previous,
+    ))
+}
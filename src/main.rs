@@ -1,104 +1,61 @@
-use anyhow::{anyhow, Context, Result};
-use chrono::{DateTime, Duration, NaiveDateTime, Utc};
-use chrono_humanize::{Accuracy, HumanTime, Tense};
+use anyhow::{Context, Result};
 use clap::Parser;
-use clio::ClioPath;
 use glob::glob;
+use log::{debug, error, warn};
 use std::env;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::process::{exit, Command};
 
 mod args;
-use args::{Args, SortBy};
-
-#[derive(Debug)]
-struct BlameData {
-    pass_filename: PathBuf,
-    last_modified: Duration,
-    found_previous_commit: bool,
-}
-
-impl BlameData {
-    const fn new(pass_filename: PathBuf, duration: Duration, found_previous: bool) -> Self {
-        Self {
-            pass_filename,
-            last_modified: duration,
-            found_previous_commit: found_previous,
+mod blame;
+mod output;
+
+use args::{Args, OutputFormat, SortBy};
+use blame::{
+    blame_data_from_timestamp, get_bulk_blame_data, secret_changed_since, GitBackend, GixBackend,
+    SubprocessBackend,
+};
+
+/// Runs `--exec`'s command template through the shell, with `{}` replaced
+/// by `pass_name`. A nonzero exit is reported as a warning rather than
+/// aborting the run, matching how per-file blame failures are handled.
+///
+/// `pass_name` is passed as `$1` rather than interpolated into the command
+/// string, so a pass name containing shell metacharacters (which `pass
+/// insert` will happily create) can't inject arbitrary commands.
+fn run_exec(template: &str, pass_name: &Path) {
+    let script = template.replace("{}", "\"$1\"");
+    match Command::new("sh")
+        .arg("-c")
+        .arg(&script)
+        .arg("sh")
+        .arg(pass_name)
+        .status()
+    {
+        Ok(status) if !status.success() => {
+            let pass_name = pass_name.display();
+            warn!("command `{script}` for {pass_name} exited with {status}");
         }
-    }
-}
-
-fn get_password_age(
-    pass_filename: &Path,
-    ignore_rev: &Vec<String>,
-    ignore_revs_file: &Vec<ClioPath>,
-) -> Result<BlameData> {
-    //Let's build up the git command
-    let mut command = Command::new("git");
-
-    //Blame in the porcalain format the first line (the password)
-    command.args(["blame", "-pL", ",1"]);
-
-    // Add in the arguments, if given
-    for ignore_rev in ignore_rev {
-        command.args(["--ignore-rev", ignore_rev]);
-    }
-
-    for ignore_revs_file in ignore_revs_file {
-        command.args([
-            "--ignore-revs-file",
-            &ignore_revs_file.path().as_os_str().to_string_lossy(),
-        ]);
-    }
-
-    command.args(["--", &pass_filename.as_os_str().to_string_lossy()]);
-
-    let git_output = command.output()?;
-
-    if !git_output.status.success() {
-        return Err(anyhow!("{}", String::from_utf8(git_output.stderr)?));
-    }
-
-    let git_stdout = String::from_utf8(git_output.stdout)?;
-
-    let mut duration = None;
-    let mut found_previous = false;
-
-    for line in git_stdout.lines() {
-        if line.starts_with("author-time") {
-            let author_time = line
-                .split_ascii_whitespace()
-                .last()
-                .with_context(|| format!("Unable to get author-time value from: {line}"))?;
-            let author_time_dt = DateTime::<Utc>::from_utc(
-                NaiveDateTime::parse_from_str(author_time, "%s")
-                    .with_context(|| format!("Unable to parse timestamp: {author_time}"))?,
-                Utc,
-            );
-            duration = Some(Utc::now() - author_time_dt);
-        } else if line.starts_with("previous") {
-            found_previous = true;
+        Ok(_) => {}
+        Err(e) => {
+            let pass_name = pass_name.display();
+            warn!("Unable to run `{script}` for {pass_name}: {e}");
         }
     }
-    if let Some(duration) = duration {
-        Ok(BlameData::new(
-            pass_filename.to_path_buf().with_extension(""),
-            duration,
-            found_previous,
-        ))
-    } else {
-        Err(anyhow!("Unable to find the author-time"))
-    }
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    env_logger::Builder::new()
+        .filter_level(args.log_level_filter())
+        .init();
+
     let password_store_dir = PathBuf::from(env!("PASSWORD_STORE_DIR"));
 
     if env::set_current_dir(password_store_dir.clone()).is_err() {
-        eprintln!(
+        error!(
             "Unable to changed working directory to {}!",
             password_store_dir.display()
         );
@@ -107,8 +64,8 @@ fn main() -> Result<()> {
 
     let password_store_git_dir = password_store_dir.join(".git");
     if !password_store_git_dir.exists() {
-        eprintln!("Unable to find {}", password_store_git_dir.display());
-        eprintln!("Please make sure you've run `pass git init`!",);
+        error!("Unable to find {}", password_store_git_dir.display());
+        error!("Please make sure you've run `pass git init`!");
         exit(1);
     }
 
@@ -134,10 +91,29 @@ fn main() -> Result<()> {
         }
     }
 
+    let bulk_ages = if args.precise {
+        None
+    } else {
+        Some(get_bulk_blame_data()?)
+    };
+
+    // Only the subprocess backend supports --ignore-rev/--ignore-revs-file,
+    // which clap already requires --precise for.
+    let backend: Option<Box<dyn GitBackend>> = if bulk_ages.is_some() {
+        None
+    } else if args.ignore_rev.is_empty() && args.ignore_revs_file.is_empty() {
+        Some(Box::new(GixBackend::open(&password_store_dir)?))
+    } else {
+        Some(Box::new(SubprocessBackend {
+            ignore_rev: &args.ignore_rev,
+            ignore_revs_file: &args.ignore_revs_file,
+        }))
+    };
+
     let mut data = Vec::new();
 
     while let Some(search_path) = search_paths.pop() {
-        println!("Searching {}", search_path.display());
+        debug!("Searching {}", search_path.display());
         let glob_matches = glob(&search_path.to_string_lossy()).with_context(|| {
             format!(
                 "Unable to search for .gpg files in {}",
@@ -148,22 +124,36 @@ fn main() -> Result<()> {
         for entry in glob_matches {
             match entry {
                 Ok(entry) => {
-                    match get_password_age(&entry, &args.ignore_rev, &args.ignore_revs_file) {
+                    let blame_data = match &bulk_ages {
+                        Some(ages) => ages
+                            .get(&entry)
+                            .with_context(|| {
+                                format!("Unable to find commit history for {}", entry.display())
+                            })
+                            .and_then(|(commit_time, found_previous)| {
+                                blame_data_from_timestamp(&entry, *commit_time, *found_previous)
+                            }),
+                        None => backend
+                            .as_deref()
+                            .expect("backend is set whenever bulk_ages is None")
+                            .blame_age(&entry),
+                    };
+                    match blame_data {
                         Ok(blame_data) => {
                             data.push(blame_data);
                         }
                         Err(e) => {
-                            eprintln!("{e}");
+                            warn!("{e}");
                         }
                     }
                 }
-                Err(e) => eprintln!("{e}"),
+                Err(e) => warn!("{e}"),
             }
             any_matches_found = true;
         }
         if !any_matches_found {
-            eprintln!(
-                "Warning: {} is not in the password store.",
+            warn!(
+                "{} is not in the password store.",
                 search_path.with_extension("").display()
             );
         }
@@ -192,23 +182,53 @@ fn main() -> Result<()> {
         }
     }
 
-    while let Some(blame_data) = data.pop() {
-        if blame_data.found_previous_commit {
-            if !args.only_unmodified {
-                println!(
-                    "{} last modified {}",
-                    blame_data.pass_filename.display(),
-                    HumanTime::from(blame_data.last_modified)
-                        .to_text_en(Accuracy::Rough, Tense::Past),
-                );
+    if let Some(rev) = &args.changed_since {
+        data.retain(|blame_data| {
+            let pass_filename = PathBuf::from(format!("{}.gpg", blame_data.pass_filename.display()));
+            match secret_changed_since(&pass_filename, rev) {
+                Ok(changed) => changed,
+                Err(e) => {
+                    warn!("{e}");
+                    false
+                }
             }
-        } else if !args.only_modified {
-            println!(
-                "{} hasn't been modified, since it was added to the store, {}",
-                blame_data.pass_filename.display(),
-                HumanTime::from(blame_data.last_modified).to_text_en(Accuracy::Rough, Tense::Past)
-            );
+        });
+    } else if let Some(rev) = &args.unchanged_since {
+        data.retain(|blame_data| {
+            let pass_filename = PathBuf::from(format!("{}.gpg", blame_data.pass_filename.display()));
+            match secret_changed_since(&pass_filename, rev) {
+                Ok(changed) => !changed,
+                Err(e) => {
+                    warn!("{e}");
+                    false
+                }
+            }
+        });
+    }
+
+    let mut visible = Vec::with_capacity(data.len());
+    while let Some(blame_data) = data.pop() {
+        let show = if blame_data.found_previous_commit {
+            !args.only_unmodified
+        } else {
+            !args.only_modified
+        };
+        if show {
+            visible.push(blame_data);
         }
     }
+
+    match args.format {
+        OutputFormat::Text => output::print_text(&visible),
+        OutputFormat::Json => output::print_json(&visible)?,
+        OutputFormat::Csv => output::print_csv(&visible),
+    }
+
+    if let Some(exec) = &args.exec {
+        for blame_data in &visible {
+            run_exec(exec, &blame_data.pass_filename);
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,78 @@
+use crate::blame::BlameData;
+use anyhow::Result;
+use chrono_humanize::{Accuracy, HumanTime, Tense};
+use serde::Serialize;
+
+/// A `BlameData` flattened into the fields machine-readable formats emit.
+#[derive(Serialize)]
+struct Entry<'a> {
+    pass_name: &'a str,
+    last_modified_unix: i64,
+    last_modified_humanized: String,
+    found_previous_commit: bool,
+}
+
+impl<'a> Entry<'a> {
+    fn from_blame_data(blame_data: &'a BlameData) -> Self {
+        Self {
+            pass_name: blame_data.pass_filename.to_str().unwrap_or_default(),
+            last_modified_unix: blame_data.last_modified_unix,
+            last_modified_humanized: HumanTime::from(blame_data.last_modified)
+                .to_text_en(Accuracy::Rough, Tense::Past),
+            found_previous_commit: blame_data.found_previous_commit,
+        }
+    }
+}
+
+/// Prints `data` as human-readable sentences, matching the tool's original
+/// output. Callers are expected to have already applied `--only-modified`/
+/// `--only-unmodified` filtering.
+pub fn print_text(data: &[BlameData]) {
+    for blame_data in data {
+        if blame_data.found_previous_commit {
+            println!(
+                "{} last modified {}",
+                blame_data.pass_filename.display(),
+                HumanTime::from(blame_data.last_modified).to_text_en(Accuracy::Rough, Tense::Past),
+            );
+        } else {
+            println!(
+                "{} hasn't been modified, since it was added to the store, {}",
+                blame_data.pass_filename.display(),
+                HumanTime::from(blame_data.last_modified).to_text_en(Accuracy::Rough, Tense::Past)
+            );
+        }
+    }
+}
+
+/// Prints `data` as a pretty-printed JSON array.
+pub fn print_json(data: &[BlameData]) -> Result<()> {
+    let entries: Vec<Entry> = data.iter().map(Entry::from_blame_data).collect();
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    Ok(())
+}
+
+/// Prints `data` as CSV, one row per password.
+pub fn print_csv(data: &[BlameData]) {
+    println!("pass_name,last_modified_unix,last_modified_humanized,found_previous_commit");
+    for blame_data in data {
+        let entry = Entry::from_blame_data(blame_data);
+        println!(
+            "{},{},{},{}",
+            csv_escape(entry.pass_name),
+            entry.last_modified_unix,
+            csv_escape(&entry.last_modified_humanized),
+            entry.found_previous_commit,
+        );
+    }
+}
+
+/// Quotes `field` if it contains a comma, quote, or newline, doubling any
+/// embedded quotes, per RFC 4180.
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}